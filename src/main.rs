@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs;
@@ -8,6 +9,7 @@ use anyhow::Context;
 use chrono::NaiveDate;
 use chrono::Utc;
 use clap_derive::Subcommand;
+use clap_derive::ValueEnum;
 use indicatif::ParallelProgressIterator;
 use indicatif::ProgressBar;
 use indicatif::ProgressIterator;
@@ -52,6 +54,9 @@ enum Commands {
         /// End date
         #[clap(long, value_parser=parse_date)]
         end_date: Option<NaiveDate>,
+        /// Color ramp to use for the heatmap
+        #[clap(long, value_enum, default_value = "green")]
+        color: ColorRamp,
     },
 
     /// Query top gems by downloads over a period
@@ -71,6 +76,36 @@ enum Commands {
     },
 }
 
+/// Color ramp used to render heatmap cells
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum ColorRamp {
+    Green,
+    Blue,
+}
+
+impl ColorRamp {
+    /// RGB for the given intensity level, 0 (no downloads) through 4 (busiest)
+    fn rgb(self, level: usize) -> (u8, u8, u8) {
+        let ramp = match self {
+            ColorRamp::Green => [
+                (0xeb, 0xed, 0xf0),
+                (0x9b, 0xe9, 0xa8),
+                (0x40, 0xc4, 0x63),
+                (0x30, 0xa1, 0x4e),
+                (0x21, 0x6e, 0x39),
+            ],
+            ColorRamp::Blue => [
+                (0xeb, 0xed, 0xf0),
+                (0xad, 0xd8, 0xe6),
+                (0x64, 0xb5, 0xf6),
+                (0x21, 0x96, 0xf3),
+                (0x0d, 0x47, 0xa1),
+            ],
+        };
+        ramp[level]
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct BetterGem {
     date: NaiveDate,
@@ -525,6 +560,149 @@ fn top(
     Ok(())
 }
 
+/// Value at `pct` (0.0-1.0) in an already-sorted slice, or 0 if empty
+fn percentile(sorted: &[i64], pct: f64) -> i64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let idx = ((sorted.len() - 1) as f64 * pct).floor() as usize;
+    sorted[idx]
+}
+
+/// Bucket a day's downloads into 5 intensity levels using quantiles of the
+/// non-zero values in the window, so both busy and quiet gems render legibly
+fn level_for(value: i64, p25: i64, p50: i64, p75: i64) -> usize {
+    if value == 0 {
+        0
+    } else if value < p25 {
+        1
+    } else if value < p50 {
+        2
+    } else if value < p75 {
+        3
+    } else {
+        4
+    }
+}
+
+#[test]
+fn test_percentile() {
+    assert_eq!(percentile(&[], 0.25), 0);
+    assert_eq!(percentile(&[7], 0.0), 7);
+    assert_eq!(percentile(&[7], 0.5), 7);
+    assert_eq!(percentile(&[7], 1.0), 7);
+    assert_eq!(percentile(&[1, 2, 3, 4, 5], 0.25), 2);
+    assert_eq!(percentile(&[1, 2, 3, 4, 5], 0.5), 3);
+    assert_eq!(percentile(&[1, 2, 3, 4, 5], 0.75), 4);
+}
+
+#[test]
+fn test_level_for() {
+    let (p25, p50, p75) = (10, 20, 30);
+    assert_eq!(level_for(0, p25, p50, p75), 0);
+    assert_eq!(level_for(9, p25, p50, p75), 1);
+    assert_eq!(level_for(10, p25, p50, p75), 2);
+    assert_eq!(level_for(20, p25, p50, p75), 3);
+    assert_eq!(level_for(30, p25, p50, p75), 4);
+    assert_eq!(level_for(31, p25, p50, p75), 4);
+}
+
+fn gem(
+    name: String,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    color: ColorRamp,
+) -> anyhow::Result<()> {
+    use chrono::Datelike;
+    use chrono::Weekday;
+
+    let path = better_gem_path(&name);
+    let better_gems = parse_better_gem_file(&path)
+        .with_context(|| format!("Failed to read downloads for gem {:?}", name))?;
+    let downloads = better_gems_to_downloads(&name, better_gems).downloads;
+
+    let end = end_date.unwrap_or_else(|| Utc::now().naive_utc().date());
+    let start = start_date.unwrap_or(end - chrono::Duration::days(365));
+
+    // `daily_downloads` is `None` for the most recent date of every gem (see
+    // `better_gems_to_downloads`), so it must stay distinct from a real `0`
+    let by_date: BTreeMap<NaiveDate, Option<i64>> = downloads
+        .into_iter()
+        .filter(|d| d.date >= start && d.date <= end)
+        .map(|d| (d.date, d.daily_downloads))
+        .collect();
+
+    let first_sunday = {
+        let mut d = start;
+        while d.weekday() != Weekday::Sun {
+            d = d.pred_opt().unwrap();
+        }
+        d
+    };
+
+    let nonzero_values = by_date
+        .values()
+        .flatten()
+        .copied()
+        .filter(|v| *v > 0)
+        .sorted()
+        .collect_vec();
+    let p25 = percentile(&nonzero_values, 0.25);
+    let p50 = percentile(&nonzero_values, 0.50);
+    let p75 = percentile(&nonzero_values, 0.75);
+
+    let mut columns: Vec<[Option<NaiveDate>; 7]> = vec![];
+    let mut column: [Option<NaiveDate>; 7] = [None; 7];
+    let mut row = 0usize;
+    let mut current = first_sunday;
+    while current <= end {
+        column[row] = Some(current);
+        row += 1;
+        if row == 7 {
+            columns.push(column);
+            column = [None; 7];
+            row = 0;
+        }
+        current = current.succ_opt().unwrap();
+    }
+    if row != 0 {
+        columns.push(column);
+    }
+
+    let mut month_line = String::new();
+    let mut last_month = None;
+    for column in &columns {
+        match column.iter().flatten().find(|date| **date >= start) {
+            Some(date) if Some(date.month()) != last_month => {
+                last_month = Some(date.month());
+                month_line.push_str(&format!("{:<3}", date.format("%b")));
+            }
+            _ => month_line.push_str("   "),
+        }
+    }
+    println!("{}", month_line);
+
+    for row in 0..7 {
+        let mut line = String::new();
+        for column in &columns {
+            match column[row] {
+                Some(date) if date >= start => match by_date.get(&date).copied().flatten() {
+                    Some(value) => {
+                        let (r, g, b) = color.rgb(level_for(value, p25, p50, p75));
+                        line.push_str(&format!("\x1b[38;2;{r};{g};{b}m██\x1b[0m "));
+                    }
+                    // no diff computed yet for this date (e.g. the most recent day)
+                    None => line.push_str("\x1b[38;2;110;110;110m░░\x1b[0m "),
+                },
+                _ => line.push_str("   "),
+            }
+        }
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
 fn main() {
     let command = Cli::parse();
 
@@ -542,6 +720,11 @@ fn main() {
             only_new,
         )
         .unwrap(),
-        _ => unreachable!(),
+        Commands::Gem {
+            name,
+            start_date,
+            end_date,
+            color,
+        } => gem(name, start_date, end_date, color).unwrap(),
     }
 }